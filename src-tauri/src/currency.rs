@@ -0,0 +1,94 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The app's historical default base currency, kept as the fallback base
+/// for a freshly created `AppData` before the first `refresh_rates` call.
+const DEFAULT_BASE: &str = "NOK";
+
+/// A cached cross-rate table, keyed by ISO currency code relative to `base`.
+/// Persisted alongside the rest of `AppData` so the app still has a rate
+/// table to convert with after a restart when the provider is unreachable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRates {
+    pub base: String,
+    pub rates: HashMap<String, f64>,
+    pub fetched_at: i64,
+}
+
+impl Default for ExchangeRates {
+    fn default() -> Self {
+        // Seed with the old hardcoded NOK/EUR pair so existing installs keep
+        // converting correctly until the first successful `refresh_rates`.
+        let mut rates = HashMap::new();
+        rates.insert(DEFAULT_BASE.to_string(), 1.0);
+        rates.insert("EUR".to_string(), 1.0 / 11.7);
+        Self {
+            base: DEFAULT_BASE.to_string(),
+            rates,
+            fetched_at: 0,
+        }
+    }
+}
+
+/// A day's worth of cache validity before `load_data` refetches the table.
+/// The `refresh_rates` command does not consult it and always fetches.
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+pub fn is_stale(table: &ExchangeRates, now: i64) -> bool {
+    now - table.fetched_at > CACHE_TTL_SECS
+}
+
+pub fn has_currency(table: &ExchangeRates, code: &str) -> bool {
+    code == table.base || table.rates.contains_key(code)
+}
+
+/// Rate of `code` relative to `table.base`. The base is implicitly `1.0`
+/// even when the provider's response doesn't include its own entry (common
+/// for FX APIs), rather than relying on `table.rates` containing it.
+fn rate_of(table: &ExchangeRates, code: &str) -> Option<Decimal> {
+    if code == table.base {
+        return Some(Decimal::ONE);
+    }
+    table.rates.get(code).and_then(|r| Decimal::from_f64_retain(*r))
+}
+
+/// Convert `amount` from `from` to `to` via the cached cross-rate table.
+/// Falls back to identity when either code is missing from the table, so a
+/// stale or empty cache degrades gracefully instead of panicking. Rates are
+/// stored as `f64` (as returned by the provider) but the conversion itself
+/// is done in `Decimal` to avoid compounding floating-point error.
+///
+/// Rates are "units of code per 1 unit of base" (e.g. the default table's
+/// `EUR => 1/11.7` means 1 NOK buys 1/11.7 EUR), so converting amount-in-`from`
+/// to amount-in-`to` is `amount * rate_to / rate_from`.
+pub fn convert(table: &ExchangeRates, amount: Decimal, from: &str, to: &str) -> Decimal {
+    if from == to {
+        return amount;
+    }
+    match (rate_of(table, from), rate_of(table, to)) {
+        (Some(rate_from), Some(rate_to)) if !rate_from.is_zero() => amount * rate_to / rate_from,
+        _ => amount,
+    }
+}
+
+/// Fetch a fresh rate table from the configured provider. The provider URL
+/// and API key are read from `CURRENCY_PROVIDER_URL` / `CURRENCY_API_KEY`,
+/// mirroring the Alpha Vantage / Finnhub / TwelveData style key config.
+/// On any network or parse failure the caller is expected to fall back to
+/// the last cached table rather than propagate the error to the user.
+pub fn fetch_rates(base: &str, now: i64) -> Result<ExchangeRates, String> {
+    let provider_url = std::env::var("CURRENCY_PROVIDER_URL")
+        .map_err(|_| "CURRENCY_PROVIDER_URL is not configured".to_string())?;
+    let api_key = std::env::var("CURRENCY_API_KEY").unwrap_or_default();
+
+    let url = format!("{}?base={}&apikey={}", provider_url, base, api_key);
+    let response = reqwest::blocking::get(&url).map_err(|e| e.to_string())?;
+    let rates: HashMap<String, f64> = response.json().map_err(|e| e.to_string())?;
+
+    Ok(ExchangeRates {
+        base: base.to_string(),
+        rates,
+        fetched_at: now,
+    })
+}
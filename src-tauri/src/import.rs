@@ -0,0 +1,193 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::BudgetEntry;
+
+/// Report returned by `import_entries` so the UI can show what happened
+/// without failing the whole batch over a single bad row.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub imported: u32,
+    pub skipped: u32,
+    pub errors: Vec<String>,
+}
+
+/// Identity used to de-duplicate an imported row against entries already on
+/// file: same date, amount, category and description is considered a repeat.
+type DedupKey = (String, Decimal, String, String);
+
+fn dedup_key(entry: &BudgetEntry) -> DedupKey {
+    (
+        entry.date.clone(),
+        entry.amount,
+        entry.category.clone(),
+        entry.description.clone(),
+    )
+}
+
+/// Columns match the layout produced by `export_to_csv`:
+/// ID,Type,Amount,Currency,Category,Date,Description
+fn parse_csv_record(
+    record: &csv::StringRecord,
+    default_currency: &str,
+) -> Result<BudgetEntry, String> {
+    let entry_type = record.get(1).ok_or("missing type column")?.to_string();
+    if entry_type != "income" && entry_type != "expense" {
+        return Err(format!(
+            "type must be 'income' or 'expense', got '{}'",
+            entry_type
+        ));
+    }
+
+    let amount_str = record.get(2).ok_or("missing amount column")?;
+    let amount: Decimal = amount_str
+        .parse()
+        .map_err(|e| format!("invalid amount '{}': {}", amount_str, e))?;
+
+    let currency = record
+        .get(3)
+        .filter(|c| !c.is_empty())
+        .unwrap_or(default_currency)
+        .to_string();
+    let category = record.get(4).unwrap_or_default().to_string();
+    let date = record.get(5).ok_or("missing date column")?.to_string();
+    let description = record.get(6).unwrap_or_default().to_string();
+
+    Ok(BudgetEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        entry_type,
+        amount,
+        currency,
+        category,
+        date,
+        description,
+    })
+}
+
+fn parse_csv(content: &str, default_currency: &str) -> (Vec<BudgetEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(content.as_bytes());
+
+    for (row, record) in reader.records().enumerate() {
+        let line = row + 2; // + header row, 1-indexed
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(format!("line {}: {}", line, e));
+                continue;
+            }
+        };
+
+        match parse_csv_record(&record, default_currency) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("line {}: {}", line, e)),
+        }
+    }
+
+    (entries, errors)
+}
+
+/// Each posting with a non-zero amount becomes its own `BudgetEntry`. Per
+/// plaintext-accounting convention (ledger-cli/hledger/beancount), an
+/// Expense-account posting is recorded positive and an Income-account
+/// posting negative, so a positive quantity is an expense and a negative one
+/// is income. The posting's account becomes the category and the
+/// transaction description carries over unchanged.
+fn parse_ledger(content: &str, default_currency: &str) -> (Vec<BudgetEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    let ledger = match ledger_parser::parse(content) {
+        Ok(ledger) => ledger,
+        Err(e) => {
+            errors.push(format!("ledger parse error: {}", e));
+            return (entries, errors);
+        }
+    };
+
+    for transaction in &ledger.transactions {
+        let date = transaction.date.format("%Y-%m-%d").to_string();
+
+        for posting in &transaction.postings {
+            let Some(posting_amount) = &posting.amount else {
+                continue;
+            };
+            let quantity = posting_amount.amount.quantity;
+            if quantity.is_zero() {
+                continue;
+            }
+
+            let currency = if posting_amount.amount.commodity.name.is_empty() {
+                default_currency.to_string()
+            } else {
+                posting_amount.amount.commodity.name.clone()
+            };
+
+            entries.push(BudgetEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                entry_type: if quantity.is_sign_positive() {
+                    "expense"
+                } else {
+                    "income"
+                }
+                .to_string(),
+                amount: quantity.abs(),
+                currency,
+                category: posting.account.clone(),
+                date: date.clone(),
+                description: transaction.description.clone(),
+            });
+        }
+    }
+
+    (entries, errors)
+}
+
+/// Parse `content` according to `format` ("csv" or "ledger"), drop rows that
+/// duplicate an entry already in `existing` by (date, amount, category,
+/// description), and return the surviving entries alongside a report.
+pub fn import(
+    format: &str,
+    content: &str,
+    default_currency: &str,
+    existing: &[BudgetEntry],
+) -> Result<(Vec<BudgetEntry>, ImportSummary), String> {
+    let (parsed, errors) = match format {
+        "csv" => parse_csv(content, default_currency),
+        "ledger" => parse_ledger(content, default_currency),
+        other => return Err(format!("unsupported import format '{}'", other)),
+    };
+
+    let mut seen: HashSet<DedupKey> = existing.iter().map(dedup_key).collect();
+    let mut survivors = Vec::new();
+    let mut skipped = 0u32;
+
+    for entry in parsed {
+        let key = dedup_key(&entry);
+        if !seen.insert(key) {
+            skipped += 1;
+            continue;
+        }
+        survivors.push(entry);
+    }
+
+    let imported = survivors.len() as u32;
+    // Already in ascending line order as pushed by parse_csv/parse_ledger;
+    // sorting lexically would break it once line numbers hit double digits
+    // ("line 10" sorts before "line 2").
+
+    Ok((
+        survivors,
+        ImportSummary {
+            imported,
+            skipped,
+            errors,
+        },
+    ))
+}
@@ -1,18 +1,26 @@
 use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::sync::Mutex;
 use tauri::State;
 use uuid::Uuid;
 
+mod currency;
+mod import;
+mod recurring;
+mod storage;
+
+use storage::Storage;
+
 // Data Models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BudgetEntry {
     pub id: String,
     #[serde(rename = "type")]
     pub entry_type: String, // "income" or "expense"
-    pub amount: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
     pub currency: String, // "NOK" or "EUR"
     pub category: String,
     pub date: String, // ISO 8601 format
@@ -23,7 +31,8 @@ pub struct BudgetEntry {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TripExpense {
     pub id: String,
-    pub amount: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
     pub category: String,
     pub description: String,
     pub date: String,
@@ -34,20 +43,31 @@ pub struct Trip {
     pub id: String,
     pub name: String,
     pub destination: String,
-    pub budget: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub budget: Decimal,
     pub currency: String,
     pub start_date: String,
     pub end_date: String,
     pub expenses: Vec<TripExpense>,
-    pub total_spent: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_spent: Decimal,
 }
 
+/// A full snapshot of the app's data, assembled on demand from the SQLite
+/// backend. No longer the backing store itself (see `AppState`) — this is
+/// just the DTO `load_data` and the legacy-JSON migration deal in.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppData {
     pub selected_currency: String,
     pub entries: Vec<BudgetEntry>,
     #[serde(default)]
     pub trips: Vec<Trip>,
+    #[serde(default)]
+    pub rates: currency::ExchangeRates,
+    #[serde(default)]
+    pub recurring: Vec<recurring::RecurringRule>,
+    #[serde(default)]
+    pub budgets: std::collections::HashMap<String, f64>,
 }
 
 impl Default for AppData {
@@ -56,22 +76,29 @@ impl Default for AppData {
             selected_currency: "NOK".to_string(),
             entries: Vec::new(),
             trips: Vec::new(),
+            rates: currency::ExchangeRates::default(),
+            recurring: Vec::new(),
+            budgets: std::collections::HashMap::new(),
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Summary {
-    pub total_income: f64,
-    pub total_expenses: f64,
-    pub net_balance: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_expenses: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net_balance: Decimal,
     pub currency: String,
 }
 
-// State wrapper
-pub struct AppState(pub Mutex<AppData>);
+// State wrapper: a pooled SQLite connection instead of an in-memory mutex.
+pub struct AppState(pub storage::SqliteStorage);
 
-// Helper function to get data file path
+// Helper function to get the legacy JSON data file path (still used as the
+// one-time migration source and nothing else).
 fn get_data_file_path() -> PathBuf {
     let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
     path.push(".budsjetto");
@@ -80,41 +107,74 @@ fn get_data_file_path() -> PathBuf {
     path
 }
 
-fn convert_currency(amount: f64, from: &str, to: &str) -> f64 {
-    if from == to {
-        return amount;
-    }
-    match (from, to) {
-        ("NOK", "EUR") => amount / 11.7,
-        ("EUR", "NOK") => amount * 11.7,
-        _ => amount,
+fn get_db_file_path() -> PathBuf {
+    let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push(".budsjetto");
+    fs::create_dir_all(&path).ok();
+    path.push("budsjetto.db");
+    path
+}
+
+fn convert_currency(
+    table: &currency::ExchangeRates,
+    amount: Decimal,
+    from: &str,
+    to: &str,
+) -> Decimal {
+    currency::convert(table, amount, from, to)
+}
+
+/// Walk every recurring rule forward to today, inserting the real entries it
+/// generates and persisting the advanced `last_generated` dates. Idempotent:
+/// calling this again before the next occurrence is due generates nothing.
+fn materialize_due(storage: &storage::SqliteStorage) -> Result<Vec<BudgetEntry>, String> {
+    let mut rules = storage.get_recurring_rules()?;
+    let today = chrono::Local::now().naive_local().date();
+    let mut generated = Vec::new();
+
+    for rule in &mut rules {
+        let (entries, last_generated) = recurring::materialize(rule, today);
+        if !entries.is_empty() {
+            for entry in &entries {
+                storage.insert_entry(entry)?;
+            }
+            rule.last_generated = last_generated;
+            // Persist this rule's advanced last_generated right away, so a
+            // crash or error on a later rule can't leave these entries
+            // without the bookkeeping that makes them idempotent to
+            // regenerate. Writing the whole rules list here is redundant
+            // with earlier iterations but never unsafe: only this rule's
+            // last_generated has changed since it was last written.
+            storage.set_recurring_rules(&rules)?;
+            generated.extend(entries);
+        }
     }
+
+    Ok(generated)
 }
 
 // Tauri Commands
 #[tauri::command]
 fn load_data(state: State<AppState>) -> Result<AppData, String> {
-    let path = get_data_file_path();
-
-    let data = if path.exists() {
-        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        AppData::default()
-    };
+    // Best-effort refresh: an expired cache shouldn't block loading the app.
+    let rates = state.0.get_rates()?;
+    let now = chrono::Local::now().timestamp();
+    if currency::is_stale(&rates, now) {
+        if let Ok(fresh) = currency::fetch_rates(&rates.base, now) {
+            state.0.set_rates(&fresh)?;
+        }
+    }
 
-    let mut app_data = state.0.lock().map_err(|e| e.to_string())?;
-    *app_data = data.clone();
+    materialize_due(&state.0)?;
 
-    Ok(data)
+    state.0.get_app_data()
 }
 
 #[tauri::command]
-fn save_data(state: State<AppState>) -> Result<(), String> {
-    let path = get_data_file_path();
-    let data = state.0.lock().map_err(|e| e.to_string())?;
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+fn save_data(_state: State<AppState>) -> Result<(), String> {
+    // Every command now persists its own change directly to SQLite, so
+    // there's no longer an in-memory buffer to flush. Kept as a no-op so
+    // the frontend's existing call site keeps working.
     Ok(())
 }
 
@@ -135,60 +195,48 @@ fn add_entry(
         return Err("Type must be 'income' or 'expense'".to_string());
     }
 
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let amount = Decimal::from_f64(amount).ok_or("Amount is not a valid number")?;
+    let currency = state.0.get_selected_currency()?;
 
     let entry = BudgetEntry {
         id: Uuid::new_v4().to_string(),
         entry_type,
         amount,
-        currency: data.selected_currency.clone(),
+        currency,
         category,
         date,
         description,
     };
 
-    data.entries.push(entry.clone());
-
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+    state.0.insert_entry(&entry)?;
 
     Ok(entry)
 }
 
 #[tauri::command]
 fn delete_entry(id: String, state: State<AppState>) -> Result<(), String> {
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
-
-    let original_len = data.entries.len();
-    data.entries.retain(|e| e.id != id);
-
-    if data.entries.len() == original_len {
-        return Err("Entry not found".to_string());
+    if state.0.delete_entry(&id)? {
+        Ok(())
+    } else {
+        Err("Entry not found".to_string())
     }
-
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
-
-    Ok(())
 }
 
 #[tauri::command]
 fn get_all_entries(state: State<AppState>) -> Result<Vec<BudgetEntry>, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let selected_currency = state.0.get_selected_currency()?;
+    let rates = state.0.get_rates()?;
 
-    let entries: Vec<BudgetEntry> = data
-        .entries
-        .iter()
-        .map(|e| {
-            let mut entry = e.clone();
-            if entry.currency != data.selected_currency {
+    let entries = state
+        .0
+        .list_entries()?
+        .into_iter()
+        .map(|mut entry| {
+            if entry.currency != selected_currency {
                 entry.amount =
-                    convert_currency(entry.amount, &entry.currency, &data.selected_currency);
-                entry.currency = data.selected_currency.clone();
+                    convert_currency(&rates, entry.amount, &entry.currency, &selected_currency)
+                        .round_dp(2);
+                entry.currency = selected_currency.clone();
             }
             entry
         })
@@ -199,16 +247,18 @@ fn get_all_entries(state: State<AppState>) -> Result<Vec<BudgetEntry>, String> {
 
 #[tauri::command]
 fn get_weekly_summary(week: u32, year: i32, state: State<AppState>) -> Result<Summary, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let selected_currency = state.0.get_selected_currency()?;
+    let rates = state.0.get_rates()?;
+    let entries = state.0.list_entries()?;
 
-    let mut total_income = 0.0;
-    let mut total_expenses = 0.0;
+    let mut total_income = Decimal::ZERO;
+    let mut total_expenses = Decimal::ZERO;
 
-    for entry in &data.entries {
+    for entry in &entries {
         if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
             if date.iso_week().week() == week && date.iso_week().year() == year {
                 let amount =
-                    convert_currency(entry.amount, &entry.currency, &data.selected_currency);
+                    convert_currency(&rates, entry.amount, &entry.currency, &selected_currency);
                 if entry.entry_type == "income" {
                     total_income += amount;
                 } else {
@@ -219,25 +269,27 @@ fn get_weekly_summary(week: u32, year: i32, state: State<AppState>) -> Result<Su
     }
 
     Ok(Summary {
-        total_income,
-        total_expenses,
-        net_balance: total_income - total_expenses,
-        currency: data.selected_currency.clone(),
+        total_income: total_income.round_dp(2),
+        total_expenses: total_expenses.round_dp(2),
+        net_balance: (total_income - total_expenses).round_dp(2),
+        currency: selected_currency,
     })
 }
 
 #[tauri::command]
 fn get_monthly_summary(month: u32, year: i32, state: State<AppState>) -> Result<Summary, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let selected_currency = state.0.get_selected_currency()?;
+    let rates = state.0.get_rates()?;
+    let entries = state.0.list_entries()?;
 
-    let mut total_income = 0.0;
-    let mut total_expenses = 0.0;
+    let mut total_income = Decimal::ZERO;
+    let mut total_expenses = Decimal::ZERO;
 
-    for entry in &data.entries {
+    for entry in &entries {
         if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
             if date.month() == month && date.year() == year {
                 let amount =
-                    convert_currency(entry.amount, &entry.currency, &data.selected_currency);
+                    convert_currency(&rates, entry.amount, &entry.currency, &selected_currency);
                 if entry.entry_type == "income" {
                     total_income += amount;
                 } else {
@@ -248,70 +300,220 @@ fn get_monthly_summary(month: u32, year: i32, state: State<AppState>) -> Result<
     }
 
     Ok(Summary {
-        total_income,
-        total_expenses,
-        net_balance: total_income - total_expenses,
-        currency: data.selected_currency.clone(),
+        total_income: total_income.round_dp(2),
+        total_expenses: total_expenses.round_dp(2),
+        net_balance: (total_income - total_expenses).round_dp(2),
+        currency: selected_currency,
     })
 }
 
 #[tauri::command]
 fn set_currency(currency: String, state: State<AppState>) -> Result<(), String> {
-    if currency != "NOK" && currency != "EUR" {
-        return Err("Currency must be 'NOK' or 'EUR'".to_string());
+    let rates = state.0.get_rates()?;
+
+    if !currency::has_currency(&rates, &currency) {
+        return Err(format!(
+            "Currency '{}' is not in the exchange-rate table; refresh rates first",
+            currency
+        ));
     }
 
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
-    data.selected_currency = currency;
+    state.0.set_selected_currency(&currency)
+}
 
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+#[tauri::command]
+fn get_currency(state: State<AppState>) -> Result<String, String> {
+    state.0.get_selected_currency()
+}
 
-    Ok(())
+#[tauri::command]
+fn refresh_rates(state: State<AppState>) -> Result<currency::ExchangeRates, String> {
+    let rates = state.0.get_rates()?;
+    let now = chrono::Local::now().timestamp();
+
+    let updated = match currency::fetch_rates(&rates.base, now) {
+        Ok(fresh) => fresh,
+        // Never had a successful fetch (still on the seeded default table):
+        // surface the error instead of silently pretending this succeeded.
+        Err(e) if rates.fetched_at == 0 => return Err(e),
+        // Provider unreachable; keep serving the last cached table.
+        Err(_) => rates,
+    };
+
+    state.0.set_rates(&updated)?;
+    Ok(updated)
 }
 
 #[tauri::command]
-fn get_currency(state: State<AppState>) -> Result<String, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
-    Ok(data.selected_currency.clone())
+fn add_recurring_rule(
+    entry_type: String,
+    amount: f64,
+    currency: String,
+    category: String,
+    description: String,
+    frequency: recurring::Frequency,
+    interval: u32,
+    anchor_date: String,
+    end_date: Option<String>,
+    state: State<AppState>,
+) -> Result<recurring::RecurringRule, String> {
+    if amount <= 0.0 {
+        return Err("Amount must be positive".to_string());
+    }
+
+    if entry_type != "income" && entry_type != "expense" {
+        return Err("Type must be 'income' or 'expense'".to_string());
+    }
+
+    // Upper-bounded well below what would push a Monthly/Yearly occurrence's
+    // computed year past what NaiveDate can represent.
+    const MAX_INTERVAL: u32 = 1000;
+    if interval == 0 || interval > MAX_INTERVAL {
+        return Err(format!("Interval must be between 1 and {}", MAX_INTERVAL));
+    }
+
+    let amount = Decimal::from_f64(amount).ok_or("Amount is not a valid number")?;
+
+    let rule = recurring::RecurringRule {
+        id: Uuid::new_v4().to_string(),
+        entry_type,
+        amount,
+        currency,
+        category,
+        description,
+        frequency,
+        interval,
+        anchor_date,
+        end_date,
+        last_generated: None,
+    };
+
+    let mut rules = state.0.get_recurring_rules()?;
+    rules.push(rule.clone());
+    state.0.set_recurring_rules(&rules)?;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+fn delete_recurring_rule(id: String, state: State<AppState>) -> Result<(), String> {
+    let mut rules = state.0.get_recurring_rules()?;
+
+    let original_len = rules.len();
+    rules.retain(|r| r.id != id);
+
+    if rules.len() == original_len {
+        return Err("Recurring rule not found".to_string());
+    }
+
+    state.0.set_recurring_rules(&rules)
+}
+
+#[tauri::command]
+fn get_recurring_rules(state: State<AppState>) -> Result<Vec<recurring::RecurringRule>, String> {
+    state.0.get_recurring_rules()
+}
+
+#[tauri::command]
+fn materialize_recurring(state: State<AppState>) -> Result<Vec<BudgetEntry>, String> {
+    materialize_due(&state.0)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CategorySummary {
     pub category: String,
-    pub total: f64,
-    pub percentage: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub percentage: Decimal,
     pub count: u32,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub limit: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub remaining: Decimal,
+    pub status: String, // "ok" | "warning" | "over"
+}
+
+/// Fraction of a category budget at which `get_category_analytics` starts
+/// reporting "warning" instead of "ok".
+const BUDGET_WARNING_THRESHOLD: f64 = 0.8;
+
+fn budget_status(total: Decimal, limit: Option<Decimal>) -> (Decimal, String) {
+    match limit {
+        None => (Decimal::ZERO, "ok".to_string()),
+        Some(limit) => {
+            let remaining = (limit - total).round_dp(2);
+            let warning_at = limit * Decimal::from_f64_retain(BUDGET_WARNING_THRESHOLD).unwrap();
+            let status = if total >= limit {
+                "over"
+            } else if total >= warning_at {
+                "warning"
+            } else {
+                "ok"
+            };
+            (remaining, status.to_string())
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CategoryAnalytics {
     pub income_by_category: Vec<CategorySummary>,
     pub expense_by_category: Vec<CategorySummary>,
-    pub total_income: f64,
-    pub total_expenses: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_expenses: Decimal,
     pub currency: String,
 }
 
+#[tauri::command]
+fn set_category_budget(
+    category: String,
+    limit: f64,
+    state: State<AppState>,
+) -> Result<(), String> {
+    if limit <= 0.0 {
+        return Err("Budget limit must be positive".to_string());
+    }
+
+    let mut budgets = state.0.get_budgets()?;
+    budgets.insert(category, limit);
+    state.0.set_budgets(&budgets)
+}
+
+#[tauri::command]
+fn get_category_budgets(
+    state: State<AppState>,
+) -> Result<std::collections::HashMap<String, f64>, String> {
+    state.0.get_budgets()
+}
+
 #[tauri::command]
 fn get_category_analytics(
     month: Option<u32>,
     year: Option<i32>,
     state: State<AppState>,
 ) -> Result<CategoryAnalytics, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let selected_currency = state.0.get_selected_currency()?;
+    let rates = state.0.get_rates()?;
+    let budgets = state.0.get_budgets()?;
+    let entries = state.0.list_entries()?;
 
-    let mut income_map: std::collections::HashMap<String, (f64, u32)> =
+    let mut income_map: std::collections::HashMap<String, (Decimal, u32)> =
         std::collections::HashMap::new();
-    let mut expense_map: std::collections::HashMap<String, (f64, u32)> =
+    let mut expense_map: std::collections::HashMap<String, (Decimal, u32)> =
         std::collections::HashMap::new();
 
-    let mut total_income = 0.0;
-    let mut total_expenses = 0.0;
+    let mut total_income = Decimal::ZERO;
+    let mut total_expenses = Decimal::ZERO;
 
-    for entry in &data.entries {
+    // Budget limits are monthly, so overspend status only makes sense when
+    // the caller scoped the query to a single month; an all-time total
+    // compared against a monthly limit would read as permanently "over".
+    let monthly_scope = month.is_some() && year.is_some();
+
+    for entry in &entries {
         // Filter by month/year if provided
         if let (Some(m), Some(y)) = (month, year) {
             if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
@@ -321,18 +523,20 @@ fn get_category_analytics(
             }
         }
 
-        let amount = convert_currency(entry.amount, &entry.currency, &data.selected_currency);
+        let amount = convert_currency(&rates, entry.amount, &entry.currency, &selected_currency);
 
         if entry.entry_type == "income" {
             total_income += amount;
-            let entry_data = income_map.entry(entry.category.clone()).or_insert((0.0, 0));
+            let entry_data = income_map
+                .entry(entry.category.clone())
+                .or_insert((Decimal::ZERO, 0));
             entry_data.0 += amount;
             entry_data.1 += 1;
         } else {
             total_expenses += amount;
             let entry_data = expense_map
                 .entry(entry.category.clone())
-                .or_insert((0.0, 0));
+                .or_insert((Decimal::ZERO, 0));
             entry_data.0 += amount;
             entry_data.1 += 1;
         }
@@ -342,46 +546,64 @@ fn get_category_analytics(
         .into_iter()
         .map(|(category, (total, count))| CategorySummary {
             category,
-            total,
-            percentage: if total_income > 0.0 {
-                (total / total_income) * 100.0
+            total: total.round_dp(2),
+            percentage: if total_income > Decimal::ZERO {
+                ((total / total_income) * Decimal::from(100)).round_dp(2)
             } else {
-                0.0
+                Decimal::ZERO
             },
             count,
+            limit: None,
+            remaining: Decimal::ZERO,
+            status: "ok".to_string(),
         })
         .collect();
 
     let expense_by_category: Vec<CategorySummary> = expense_map
         .into_iter()
-        .map(|(category, (total, count))| CategorySummary {
-            category,
-            total,
-            percentage: if total_expenses > 0.0 {
-                (total / total_expenses) * 100.0
+        .map(|(category, (total, count))| {
+            let (limit, remaining, status) = if monthly_scope {
+                let limit = budgets
+                    .get(&category)
+                    .and_then(|l| Decimal::from_f64_retain(*l));
+                let (remaining, status) = budget_status(total, limit);
+                (limit, remaining, status)
             } else {
-                0.0
-            },
-            count,
+                (None, Decimal::ZERO, "ok".to_string())
+            };
+
+            CategorySummary {
+                category,
+                total: total.round_dp(2),
+                percentage: if total_expenses > Decimal::ZERO {
+                    ((total / total_expenses) * Decimal::from(100)).round_dp(2)
+                } else {
+                    Decimal::ZERO
+                },
+                count,
+                limit,
+                remaining,
+                status,
+            }
         })
         .collect();
 
     Ok(CategoryAnalytics {
         income_by_category,
         expense_by_category,
-        total_income,
-        total_expenses,
-        currency: data.selected_currency.clone(),
+        total_income: total_income.round_dp(2),
+        total_expenses: total_expenses.round_dp(2),
+        currency: selected_currency,
     })
 }
 
 #[tauri::command]
 fn export_to_csv(state: State<AppState>) -> Result<String, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let entries = state.0.list_entries()?;
 
     let mut csv_content = String::from("ID,Type,Amount,Currency,Category,Date,Description\n");
 
-    for entry in &data.entries {
+    for entry in &entries {
         let escaped_description = entry.description.replace('"', "\"\"");
         csv_content.push_str(&format!(
             "{},{},{},{},{},{},\"{}\"\n",
@@ -407,19 +629,43 @@ fn export_to_csv(state: State<AppState>) -> Result<String, String> {
     Ok(path.to_string_lossy().to_string())
 }
 
+#[tauri::command]
+fn import_entries(
+    file_path: String,
+    format: String,
+    state: State<AppState>,
+) -> Result<import::ImportSummary, String> {
+    let content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+
+    let selected_currency = state.0.get_selected_currency()?;
+    let existing = state.0.list_entries()?;
+    let (survivors, summary) = import::import(&format, &content, &selected_currency, &existing)?;
+
+    for entry in &survivors {
+        state.0.insert_entry(entry)?;
+    }
+
+    Ok(summary)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MonthlyTrend {
     pub month: u32,
     pub year: i32,
     pub month_name: String,
-    pub income: f64,
-    pub expenses: f64,
-    pub net: f64,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub expenses: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net: Decimal,
 }
 
 #[tauri::command]
 fn get_monthly_trends(months: u32, state: State<AppState>) -> Result<Vec<MonthlyTrend>, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let selected_currency = state.0.get_selected_currency()?;
+    let rates = state.0.get_rates()?;
+    let entries = state.0.list_entries()?;
 
     let now = chrono::Local::now().naive_local().date();
     let mut trends: Vec<MonthlyTrend> = Vec::new();
@@ -429,14 +675,18 @@ fn get_monthly_trends(months: u32, state: State<AppState>) -> Result<Vec<Monthly
         let month = target_date.month();
         let year = target_date.year();
 
-        let mut income = 0.0;
-        let mut expenses = 0.0;
+        let mut income = Decimal::ZERO;
+        let mut expenses = Decimal::ZERO;
 
-        for entry in &data.entries {
+        for entry in &entries {
             if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
                 if date.month() == month && date.year() == year {
-                    let amount =
-                        convert_currency(entry.amount, &entry.currency, &data.selected_currency);
+                    let amount = convert_currency(
+                        &rates,
+                        entry.amount,
+                        &entry.currency,
+                        &selected_currency,
+                    );
                     if entry.entry_type == "income" {
                         income += amount;
                     } else {
@@ -454,9 +704,9 @@ fn get_monthly_trends(months: u32, state: State<AppState>) -> Result<Vec<Monthly
             month,
             year,
             month_name: month_names[(month - 1) as usize].to_string(),
-            income,
-            expenses,
-            net: income - expenses,
+            income: income.round_dp(2),
+            expenses: expenses.round_dp(2),
+            net: (income - expenses).round_dp(2),
         });
     }
 
@@ -464,6 +714,157 @@ fn get_monthly_trends(months: u32, state: State<AppState>) -> Result<Vec<Monthly
     Ok(trends)
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CashFlowMonth {
+    pub month: u32,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub expenses: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CashFlowCategoryRow {
+    pub category: String,
+    pub months: Vec<CashFlowMonth>, // Jan..Dec, one entry per calendar month
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_expenses: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CashFlowMonthTotal {
+    pub month: u32,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub expenses: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub closing_balance: Decimal,
+}
+
+/// A categories × months pivot for `year`, normalized into `currency`, with a
+/// grand-total row (`monthly_totals`) carrying a running `closing_balance`
+/// forward from January through December.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CashFlowReport {
+    pub year: i32,
+    pub currency: String,
+    pub categories: Vec<CashFlowCategoryRow>,
+    pub monthly_totals: Vec<CashFlowMonthTotal>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_income: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_expenses: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub closing_balance: Decimal,
+}
+
+#[tauri::command]
+fn generate_cash_flow_report(
+    year: i32,
+    currency: String,
+    state: State<AppState>,
+) -> Result<CashFlowReport, String> {
+    let rates = state.0.get_rates()?;
+
+    if !currency::has_currency(&rates, &currency) {
+        return Err(format!(
+            "Currency '{}' is not in the exchange-rate table; refresh rates first",
+            currency
+        ));
+    }
+
+    let entries = state.0.list_entries()?;
+
+    let mut category_cells: std::collections::HashMap<String, [(Decimal, Decimal); 12]> =
+        std::collections::HashMap::new();
+    let mut monthly_income = [Decimal::ZERO; 12];
+    let mut monthly_expenses = [Decimal::ZERO; 12];
+
+    for entry in &entries {
+        let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") else {
+            continue;
+        };
+        if date.year() != year {
+            continue;
+        }
+
+        let month_idx = (date.month() - 1) as usize;
+        let amount = convert_currency(&rates, entry.amount, &entry.currency, &currency);
+        let cells = category_cells
+            .entry(entry.category.clone())
+            .or_insert([(Decimal::ZERO, Decimal::ZERO); 12]);
+
+        if entry.entry_type == "income" {
+            cells[month_idx].0 += amount;
+            monthly_income[month_idx] += amount;
+        } else {
+            cells[month_idx].1 += amount;
+            monthly_expenses[month_idx] += amount;
+        }
+    }
+
+    let mut categories: Vec<CashFlowCategoryRow> = category_cells
+        .into_iter()
+        .map(|(category, cells)| {
+            let months = cells
+                .iter()
+                .enumerate()
+                .map(|(i, (income, expenses))| CashFlowMonth {
+                    month: (i + 1) as u32,
+                    income: income.round_dp(2),
+                    expenses: expenses.round_dp(2),
+                })
+                .collect();
+            let total_income: Decimal = cells.iter().map(|(income, _)| *income).sum();
+            let total_expenses: Decimal = cells.iter().map(|(_, expenses)| *expenses).sum();
+
+            CashFlowCategoryRow {
+                category,
+                months,
+                total_income: total_income.round_dp(2),
+                total_expenses: total_expenses.round_dp(2),
+            }
+        })
+        .collect();
+    categories.sort_by(|a, b| a.category.cmp(&b.category));
+
+    let mut closing_balance = Decimal::ZERO;
+    let monthly_totals: Vec<CashFlowMonthTotal> = (0..12)
+        .map(|i| {
+            let income = monthly_income[i];
+            let expenses = monthly_expenses[i];
+            let net = income - expenses;
+            closing_balance += net;
+
+            CashFlowMonthTotal {
+                month: (i + 1) as u32,
+                income: income.round_dp(2),
+                expenses: expenses.round_dp(2),
+                net: net.round_dp(2),
+                closing_balance: closing_balance.round_dp(2),
+            }
+        })
+        .collect();
+
+    let total_income: Decimal = monthly_income.iter().sum();
+    let total_expenses: Decimal = monthly_expenses.iter().sum();
+
+    Ok(CashFlowReport {
+        year,
+        currency,
+        categories,
+        monthly_totals,
+        total_income: total_income.round_dp(2),
+        total_expenses: total_expenses.round_dp(2),
+        closing_balance: closing_balance.round_dp(2),
+    })
+}
+
 // Trip Budget Commands
 #[tauri::command]
 fn create_trip(
@@ -478,56 +879,59 @@ fn create_trip(
         return Err("Budget must be positive".to_string());
     }
 
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let budget = Decimal::from_f64(budget).ok_or("Budget is not a valid number")?;
+    let currency = state.0.get_selected_currency()?;
 
     let trip = Trip {
         id: Uuid::new_v4().to_string(),
         name,
         destination,
         budget,
-        currency: data.selected_currency.clone(),
+        currency,
         start_date,
         end_date,
         expenses: Vec::new(),
-        total_spent: 0.0,
+        total_spent: Decimal::ZERO,
     };
 
-    data.trips.push(trip.clone());
-
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+    state.0.insert_trip(&trip)?;
 
     Ok(trip)
 }
 
 #[tauri::command]
 fn get_trips(state: State<AppState>) -> Result<Vec<Trip>, String> {
-    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let selected_currency = state.0.get_selected_currency()?;
+    let rates = state.0.get_rates()?;
 
     // Convert trips to current currency if needed
-    let trips: Vec<Trip> = data
-        .trips
-        .iter()
-        .map(|t| {
-            let mut trip = t.clone();
-            if trip.currency != data.selected_currency {
+    let trips: Vec<Trip> = state
+        .0
+        .list_trips()?
+        .into_iter()
+        .map(|mut trip| {
+            if trip.currency != selected_currency {
                 trip.budget =
-                    convert_currency(trip.budget, &trip.currency, &data.selected_currency);
-                trip.total_spent =
-                    convert_currency(trip.total_spent, &trip.currency, &data.selected_currency);
+                    convert_currency(&rates, trip.budget, &trip.currency, &selected_currency)
+                        .round_dp(2);
+                trip.total_spent = convert_currency(
+                    &rates,
+                    trip.total_spent,
+                    &trip.currency,
+                    &selected_currency,
+                )
+                .round_dp(2);
                 trip.expenses = trip
                     .expenses
-                    .iter()
-                    .map(|e| {
-                        let mut exp = e.clone();
+                    .into_iter()
+                    .map(|mut exp| {
                         exp.amount =
-                            convert_currency(exp.amount, &trip.currency, &data.selected_currency);
+                            convert_currency(&rates, exp.amount, &trip.currency, &selected_currency)
+                                .round_dp(2);
                         exp
                     })
                     .collect();
-                trip.currency = data.selected_currency.clone();
+                trip.currency = selected_currency.clone();
             }
             trip
         })
@@ -549,13 +953,11 @@ fn add_trip_expense(
         return Err("Amount must be positive".to_string());
     }
 
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let amount = Decimal::from_f64(amount).ok_or("Amount is not a valid number")?;
 
-    let trip = data
-        .trips
-        .iter_mut()
-        .find(|t| t.id == trip_id)
-        .ok_or("Trip not found")?;
+    if state.0.get_trip(&trip_id)?.is_none() {
+        return Err("Trip not found".to_string());
+    }
 
     let expense = TripExpense {
         id: Uuid::new_v4().to_string(),
@@ -565,34 +967,19 @@ fn add_trip_expense(
         date,
     };
 
-    trip.expenses.push(expense.clone());
-    trip.total_spent += amount;
-
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+    state.0.insert_trip_expense(&trip_id, &expense)?;
+    state.0.adjust_trip_total_spent(&trip_id, amount)?;
 
     Ok(expense)
 }
 
 #[tauri::command]
 fn delete_trip(trip_id: String, state: State<AppState>) -> Result<(), String> {
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
-
-    let original_len = data.trips.len();
-    data.trips.retain(|t| t.id != trip_id);
-
-    if data.trips.len() == original_len {
-        return Err("Trip not found".to_string());
+    if state.0.delete_trip(&trip_id)? {
+        Ok(())
+    } else {
+        Err("Trip not found".to_string())
     }
-
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
-
-    Ok(())
 }
 
 #[tauri::command]
@@ -601,37 +988,35 @@ fn delete_trip_expense(
     expense_id: String,
     state: State<AppState>,
 ) -> Result<(), String> {
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
-
-    let trip = data
-        .trips
-        .iter_mut()
-        .find(|t| t.id == trip_id)
-        .ok_or("Trip not found")?;
+    let trip = state.0.get_trip(&trip_id)?.ok_or("Trip not found")?;
 
     let expense = trip
         .expenses
         .iter()
         .find(|e| e.id == expense_id)
         .ok_or("Expense not found")?;
-
     let expense_amount = expense.amount;
-    trip.expenses.retain(|e| e.id != expense_id);
-    trip.total_spent -= expense_amount;
 
-    // Save after modification
-    let path = get_data_file_path();
-    let content = serde_json::to_string_pretty(&*data).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+    if !state.0.delete_trip_expense(&trip_id, &expense_id)? {
+        return Err("Expense not found".to_string());
+    }
+
+    state.0.adjust_trip_total_spent(&trip_id, -expense_amount)?;
 
     Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let storage = storage::SqliteStorage::open(&get_db_file_path())
+        .expect("failed to open budsjetto database");
+    storage
+        .migrate_from_json(&get_data_file_path())
+        .expect("failed to migrate legacy budget_data.json");
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(AppState(Mutex::new(AppData::default())))
+        .manage(AppState(storage))
         .invoke_handler(tauri::generate_handler![
             load_data,
             save_data,
@@ -642,9 +1027,18 @@ pub fn run() {
             get_monthly_summary,
             set_currency,
             get_currency,
+            refresh_rates,
+            add_recurring_rule,
+            delete_recurring_rule,
+            get_recurring_rules,
+            materialize_recurring,
+            set_category_budget,
+            get_category_budgets,
             get_category_analytics,
             export_to_csv,
+            import_entries,
             get_monthly_trends,
+            generate_cash_flow_report,
             create_trip,
             get_trips,
             add_trip_expense,
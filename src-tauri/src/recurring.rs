@@ -0,0 +1,127 @@
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::BudgetEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A rule that materializes into a real `BudgetEntry` every time it comes due.
+/// `last_generated` tracks the last occurrence already turned into an entry,
+/// so `materialize` can be replayed on every `load_data` without duplicating.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurringRule {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub entry_type: String, // "income" or "expense"
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub currency: String,
+    pub category: String,
+    pub description: String,
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub anchor_date: String,
+    pub end_date: Option<String>,
+    pub last_generated: Option<String>,
+}
+
+fn parse(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Clamp `day` to the last valid day of `year`-`month`, so a Monthly rule
+/// anchored on the 31st still fires (on the 28th/30th) in shorter months.
+/// Returns `None` instead of panicking when `year`/`month` fall outside the
+/// range `NaiveDate` can represent.
+fn clamp_to_month(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    let last_day = NaiveDate::from_ymd_opt(year, month, 1)?
+        .with_day(1)?
+        .checked_add_months(chrono::Months::new(1))?
+        .pred_opt()?
+        .day();
+    NaiveDate::from_ymd_opt(year, month, day.min(last_day))
+}
+
+/// Advance `from` by one occurrence of `frequency`/`interval`, clamping
+/// Monthly/Yearly occurrences to the anchor day-of-month. Returns `None`
+/// when the step would overflow the range `NaiveDate`/`i32` can represent,
+/// which a sufficiently large `interval` can otherwise drive into.
+fn step(from: NaiveDate, anchor_day: u32, frequency: Frequency, interval: u32) -> Option<NaiveDate> {
+    match frequency {
+        Frequency::Daily => from.checked_add_signed(chrono::Duration::days(interval as i64)),
+        Frequency::Weekly => from.checked_add_signed(chrono::Duration::weeks(interval as i64)),
+        Frequency::Monthly => {
+            let total_months =
+                from.year() as i64 * 12 + (from.month() as i64 - 1) + interval as i64;
+            let year = i32::try_from(total_months.div_euclid(12)).ok()?;
+            let month = (total_months.rem_euclid(12)) as u32 + 1;
+            clamp_to_month(year, month, anchor_day)
+        }
+        Frequency::Yearly => {
+            let year = from.year().checked_add(i32::try_from(interval).ok()?)?;
+            clamp_to_month(year, from.month(), anchor_day)
+        }
+    }
+}
+
+/// Walk a rule forward from its last generated occurrence (or its anchor
+/// date, whichever is later) up to and including `today`, returning one
+/// `BudgetEntry` per due occurrence and the new `last_generated` date.
+/// Occurrences past `end_date` are dropped. Idempotent: calling this again
+/// with the returned `last_generated` produces no further entries until the
+/// next occurrence is actually due.
+pub fn materialize(rule: &RecurringRule, today: NaiveDate) -> (Vec<BudgetEntry>, Option<String>) {
+    let Some(anchor) = parse(&rule.anchor_date) else {
+        return (Vec::new(), rule.last_generated.clone());
+    };
+    let anchor_day = anchor.day();
+    let end_date = rule.end_date.as_deref().and_then(parse);
+
+    let mut occurrence = match rule.last_generated.as_deref().and_then(parse) {
+        Some(last) => match step(last, anchor_day, rule.frequency, rule.interval) {
+            Some(next) => next,
+            None => return (Vec::new(), rule.last_generated.clone()),
+        },
+        None => anchor,
+    };
+
+    let mut entries = Vec::new();
+    let mut last_generated = rule.last_generated.clone();
+
+    while occurrence <= today {
+        if let Some(end) = end_date {
+            if occurrence > end {
+                break;
+            }
+        }
+
+        entries.push(BudgetEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            entry_type: rule.entry_type.clone(),
+            amount: rule.amount,
+            currency: rule.currency.clone(),
+            category: rule.category.clone(),
+            date: occurrence.format("%Y-%m-%d").to_string(),
+            description: rule.description.clone(),
+        });
+        last_generated = Some(occurrence.format("%Y-%m-%d").to_string());
+
+        // An interval large enough to overflow NaiveDate's range stops the
+        // walk here rather than panicking; whatever was generated so far is
+        // still returned and recorded.
+        occurrence = match step(occurrence, anchor_day, rule.frequency, rule.interval) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    (entries, last_generated)
+}
@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, OptionalExtension};
+use rust_decimal::Decimal;
+
+use crate::currency::ExchangeRates;
+use crate::recurring::RecurringRule;
+use crate::{AppData, BudgetEntry, Trip, TripExpense};
+
+/// Storage backend for `AppData`. `SqliteStorage` is the only implementation,
+/// but the trait keeps the command handlers in `lib.rs` decoupled from the
+/// embedded-database detail so a future backend can drop in without
+/// touching every command.
+pub trait Storage: Send + Sync {
+    fn get_app_data(&self) -> Result<AppData, String>;
+
+    fn list_entries(&self) -> Result<Vec<BudgetEntry>, String>;
+    fn insert_entry(&self, entry: &BudgetEntry) -> Result<(), String>;
+    fn delete_entry(&self, id: &str) -> Result<bool, String>;
+
+    fn list_trips(&self) -> Result<Vec<Trip>, String>;
+    fn get_trip(&self, trip_id: &str) -> Result<Option<Trip>, String>;
+    fn insert_trip(&self, trip: &Trip) -> Result<(), String>;
+    fn delete_trip(&self, trip_id: &str) -> Result<bool, String>;
+    fn insert_trip_expense(&self, trip_id: &str, expense: &TripExpense) -> Result<(), String>;
+    fn delete_trip_expense(&self, trip_id: &str, expense_id: &str) -> Result<bool, String>;
+    /// Atomically add `delta` (negative to subtract) to a trip's
+    /// `total_spent` in SQL, so two concurrent trip-expense calls against the
+    /// same trip can't race a read-then-write through the connection pool.
+    fn adjust_trip_total_spent(&self, trip_id: &str, delta: Decimal) -> Result<(), String>;
+
+    fn get_selected_currency(&self) -> Result<String, String>;
+    fn set_selected_currency(&self, currency: &str) -> Result<(), String>;
+
+    fn get_rates(&self) -> Result<ExchangeRates, String>;
+    fn set_rates(&self, rates: &ExchangeRates) -> Result<(), String>;
+
+    fn get_recurring_rules(&self) -> Result<Vec<RecurringRule>, String>;
+    fn set_recurring_rules(&self, rules: &[RecurringRule]) -> Result<(), String>;
+
+    fn get_budgets(&self) -> Result<HashMap<String, f64>, String>;
+    fn set_budgets(&self, budgets: &HashMap<String, f64>) -> Result<(), String>;
+}
+
+/// Pooled SQLite persistence. Every mutation is a per-row insert/delete
+/// against `entries` / `trips` / `trip_expenses`, so a write no longer
+/// touches unrelated rows or risks corrupting the whole dataset if the
+/// process dies mid-write. `rates` / `recurring` / `budgets` change rarely
+/// and have no natural row identity, so they live as JSON blobs in the
+/// `settings` key/value table instead of their own tables.
+pub struct SqliteStorage {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+fn decimal_to_sql(amount: Decimal) -> String {
+    amount.to_string()
+}
+
+fn decimal_from_sql(text: &str) -> Result<Decimal, String> {
+    Decimal::from_str(text).map_err(|e| format!("corrupt amount '{}': {}", text, e))
+}
+
+impl SqliteStorage {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let manager = SqliteConnectionManager::file(db_path);
+        let pool = Pool::new(manager).map_err(|e| e.to_string())?;
+        let storage = Self { pool };
+        storage.init_schema()?;
+        Ok(storage)
+    }
+
+    fn conn(&self) -> Result<r2d2::PooledConnection<SqliteConnectionManager>, String> {
+        self.pool.get().map_err(|e| e.to_string())
+    }
+
+    fn init_schema(&self) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS entries (
+                id TEXT PRIMARY KEY,
+                entry_type TEXT NOT NULL,
+                amount TEXT NOT NULL,
+                currency TEXT NOT NULL,
+                category TEXT NOT NULL,
+                date TEXT NOT NULL,
+                description TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS trips (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                destination TEXT NOT NULL,
+                budget TEXT NOT NULL,
+                currency TEXT NOT NULL,
+                start_date TEXT NOT NULL,
+                end_date TEXT NOT NULL,
+                total_spent TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS trip_expenses (
+                id TEXT PRIMARY KEY,
+                trip_id TEXT NOT NULL REFERENCES trips(id),
+                amount TEXT NOT NULL,
+                category TEXT NOT NULL,
+                description TEXT NOT NULL,
+                date TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            ",
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// One-time migration: if a legacy `budget_data.json` exists and it
+    /// hasn't been imported yet, import it wholesale. Safe to call on every
+    /// startup; completion is tracked with a `migrated` settings flag rather
+    /// than inferred from `entries` being non-empty, since the user deleting
+    /// every entry after a real migration must not trigger a re-import.
+    pub fn migrate_from_json(&self, json_path: &Path) -> Result<(), String> {
+        if !json_path.exists() {
+            return Ok(());
+        }
+
+        if self.get_setting("migrated")?.is_some() {
+            return Ok(());
+        }
+
+        let content = std::fs::read_to_string(json_path).map_err(|e| e.to_string())?;
+        let data: AppData = serde_json::from_str(&content).unwrap_or_default();
+
+        for entry in &data.entries {
+            self.insert_entry(entry)?;
+        }
+        for trip in &data.trips {
+            self.insert_trip(trip)?;
+            for expense in &trip.expenses {
+                self.insert_trip_expense(&trip.id, expense)?;
+            }
+        }
+        self.set_selected_currency(&data.selected_currency)?;
+        self.set_rates(&data.rates)?;
+        self.set_recurring_rules(&data.recurring)?;
+        self.set_budgets(&data.budgets)?;
+        self.set_setting("migrated", "1")?;
+
+        Ok(())
+    }
+
+    fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<BudgetEntry> {
+        let amount_text: String = row.get(2)?;
+        Ok(BudgetEntry {
+            id: row.get(0)?,
+            entry_type: row.get(1)?,
+            amount: decimal_from_sql(&amount_text).unwrap_or_default(),
+            currency: row.get(3)?,
+            category: row.get(4)?,
+            date: row.get(5)?,
+            description: row.get(6)?,
+        })
+    }
+
+    fn row_to_trip_expense(row: &rusqlite::Row) -> rusqlite::Result<TripExpense> {
+        let amount_text: String = row.get(1)?;
+        Ok(TripExpense {
+            id: row.get(0)?,
+            amount: decimal_from_sql(&amount_text).unwrap_or_default(),
+            category: row.get(2)?,
+            description: row.get(3)?,
+            date: row.get(4)?,
+        })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn get_app_data(&self) -> Result<AppData, String> {
+        Ok(AppData {
+            selected_currency: self.get_selected_currency()?,
+            entries: self.list_entries()?,
+            trips: self.list_trips()?,
+            rates: self.get_rates()?,
+            recurring: self.get_recurring_rules()?,
+            budgets: self.get_budgets()?,
+        })
+    }
+
+    fn list_entries(&self) -> Result<Vec<BudgetEntry>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, entry_type, amount, currency, category, date, description
+                 FROM entries",
+            )
+            .map_err(|e| e.to_string())?;
+        let entries = stmt
+            .query_map([], Self::row_to_entry)
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(entries)
+    }
+
+    fn insert_entry(&self, entry: &BudgetEntry) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO entries (id, entry_type, amount, currency, category, date, description)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.id,
+                entry.entry_type,
+                decimal_to_sql(entry.amount),
+                entry.currency,
+                entry.category,
+                entry.date,
+                entry.description,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn delete_entry(&self, id: &str) -> Result<bool, String> {
+        let conn = self.conn()?;
+        let rows = conn
+            .execute("DELETE FROM entries WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(rows > 0)
+    }
+
+    fn list_trips(&self) -> Result<Vec<Trip>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, destination, budget, currency, start_date, end_date, total_spent
+                 FROM trips",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut trips = stmt
+            .query_map([], |row| {
+                let budget_text: String = row.get(3)?;
+                let total_spent_text: String = row.get(7)?;
+                Ok(Trip {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    destination: row.get(2)?,
+                    budget: decimal_from_sql(&budget_text).unwrap_or_default(),
+                    currency: row.get(4)?,
+                    start_date: row.get(5)?,
+                    end_date: row.get(6)?,
+                    expenses: Vec::new(),
+                    total_spent: decimal_from_sql(&total_spent_text).unwrap_or_default(),
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut expense_stmt = conn
+            .prepare("SELECT id, amount, category, description, date FROM trip_expenses WHERE trip_id = ?1")
+            .map_err(|e| e.to_string())?;
+        for trip in &mut trips {
+            trip.expenses = expense_stmt
+                .query_map(params![trip.id], Self::row_to_trip_expense)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(trips)
+    }
+
+    fn get_trip(&self, trip_id: &str) -> Result<Option<Trip>, String> {
+        Ok(self.list_trips()?.into_iter().find(|t| t.id == trip_id))
+    }
+
+    fn insert_trip(&self, trip: &Trip) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO trips (id, name, destination, budget, currency, start_date, end_date, total_spent)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                trip.id,
+                trip.name,
+                trip.destination,
+                decimal_to_sql(trip.budget),
+                trip.currency,
+                trip.start_date,
+                trip.end_date,
+                decimal_to_sql(trip.total_spent),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn delete_trip(&self, trip_id: &str) -> Result<bool, String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "DELETE FROM trip_expenses WHERE trip_id = ?1",
+            params![trip_id],
+        )
+        .map_err(|e| e.to_string())?;
+        let rows = conn
+            .execute("DELETE FROM trips WHERE id = ?1", params![trip_id])
+            .map_err(|e| e.to_string())?;
+        Ok(rows > 0)
+    }
+
+    fn insert_trip_expense(&self, trip_id: &str, expense: &TripExpense) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO trip_expenses (id, trip_id, amount, category, description, date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                expense.id,
+                trip_id,
+                decimal_to_sql(expense.amount),
+                expense.category,
+                expense.description,
+                expense.date,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn delete_trip_expense(&self, trip_id: &str, expense_id: &str) -> Result<bool, String> {
+        let conn = self.conn()?;
+        let rows = conn
+            .execute(
+                "DELETE FROM trip_expenses WHERE id = ?1 AND trip_id = ?2",
+                params![expense_id, trip_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(rows > 0)
+    }
+
+    fn adjust_trip_total_spent(&self, trip_id: &str, delta: Decimal) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE trips SET total_spent = total_spent + ?1 WHERE id = ?2",
+            params![decimal_to_sql(delta), trip_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn get_selected_currency(&self) -> Result<String, String> {
+        Ok(self
+            .get_setting("selected_currency")?
+            .unwrap_or_else(|| "NOK".to_string()))
+    }
+
+    fn set_selected_currency(&self, currency: &str) -> Result<(), String> {
+        self.set_setting("selected_currency", currency)
+    }
+
+    fn get_rates(&self) -> Result<ExchangeRates, String> {
+        match self.get_setting("rates")? {
+            Some(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+            None => Ok(ExchangeRates::default()),
+        }
+    }
+
+    fn set_rates(&self, rates: &ExchangeRates) -> Result<(), String> {
+        let json = serde_json::to_string(rates).map_err(|e| e.to_string())?;
+        self.set_setting("rates", &json)
+    }
+
+    fn get_recurring_rules(&self) -> Result<Vec<RecurringRule>, String> {
+        match self.get_setting("recurring")? {
+            Some(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn set_recurring_rules(&self, rules: &[RecurringRule]) -> Result<(), String> {
+        let json = serde_json::to_string(rules).map_err(|e| e.to_string())?;
+        self.set_setting("recurring", &json)
+    }
+
+    fn get_budgets(&self) -> Result<HashMap<String, f64>, String> {
+        match self.get_setting("budgets")? {
+            Some(json) => Ok(serde_json::from_str(&json).unwrap_or_default()),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    fn set_budgets(&self, budgets: &HashMap<String, f64>) -> Result<(), String> {
+        let json = serde_json::to_string(budgets).map_err(|e| e.to_string())?;
+        self.set_setting("budgets", &json)
+    }
+}
+
+impl SqliteStorage {
+    fn get_setting(&self, key: &str) -> Result<Option<String>, String> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}